@@ -0,0 +1,114 @@
+extern crate proc_macro;
+extern crate proc_macro2;
+extern crate syn;
+#[macro_use]
+extern crate quote;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+/// Implements `mysql::FromRow` and `mysql::Storable` for a struct with named
+/// fields. Each field is read with `Row::get` from the column of the same name.
+///
+/// Field attributes:
+///
+/// - `#[mysql(rename = "col")]` reads the field from column `col` instead
+/// - `#[mysql(default)]` uses `Default::default()` when the column is NULL or
+///   missing from the result set
+/// - `#[mysql(flatten)]` reads the field as a nested `FromRow` struct from the
+///   same row
+#[proc_macro_derive(Storable, attributes(mysql))]
+pub fn derive_storable(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+struct FieldAttrs {
+    rename: Option<String>,
+    default: bool,
+    flatten: bool,
+}
+
+impl FieldAttrs {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut attrs = FieldAttrs{ rename: None, default: false, flatten: false };
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("mysql")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    attrs.rename = Some(name.value());
+                } else if meta.path.is_ident("default") {
+                    attrs.default = true;
+                } else if meta.path.is_ident("flatten") {
+                    attrs.flatten = true;
+                } else {
+                    return Err(meta.error("expected `rename`, `default` or `flatten`"));
+                }
+                Ok(())
+            })?;
+        }
+        if attrs.flatten && (attrs.rename.is_some() || attrs.default) {
+            return Err(syn::Error::new_spanned(field, "`flatten` cannot be combined with other mysql attributes"));
+        }
+        Ok(attrs)
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(input, "Storable can only be derived for structs with named fields")),
+        },
+        _ => return Err(syn::Error::new_spanned(input, "Storable can only be derived for structs")),
+    };
+
+    let mut inits = vec![];
+    for field in fields {
+        let attrs = FieldAttrs::parse(field)?;
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let column = attrs.rename.unwrap_or_else(|| ident.to_string());
+
+        inits.push(if attrs.flatten {
+            quote!{ #ident: <#ty as ::mysql::FromRow>::from_row(row)?, }
+        } else if attrs.default {
+            quote!{
+                #ident: match row.get::<::std::option::Option<#ty>>(#column) {
+                    ::std::result::Result::Ok(::std::option::Option::Some(value)) => value,
+                    ::std::result::Result::Ok(::std::option::Option::None) |
+                    ::std::result::Result::Err(::mysql::Error::UnknownColumn(_)) => ::std::default::Default::default(),
+                    ::std::result::Result::Err(err) => return ::std::result::Result::Err(err),
+                },
+            }
+        } else {
+            quote!{ #ident: row.get::<#ty>(#column)?, }
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote!{
+        impl #impl_generics ::mysql::FromRow for #name #ty_generics #where_clause {
+            fn from_row(row: &mut ::mysql::Row) -> ::std::result::Result<Self, ::mysql::Error> {
+                ::std::result::Result::Ok(#name {
+                    #(#inits)*
+                })
+            }
+        }
+
+        impl #impl_generics ::mysql::Storable for #name #ty_generics #where_clause {
+            type Kind = Self;
+            fn store<__Rows>(rows: __Rows) -> ::std::result::Result<::std::vec::Vec<Self>, ::mysql::Error>
+                where __Rows: ::std::iter::Iterator<Item=::mysql::Row>
+            {
+                ::mysql::store_rows(rows)
+            }
+        }
+    })
+}
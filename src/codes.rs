@@ -0,0 +1,65 @@
+//! MySQL error codes as returned by `Error::code()`.
+//!
+//! Server errors (`ER_*`) are in the 1000-1999 and 3000+ ranges, client library
+//! errors (`CR_*`) in 2000-2999. Only the codes the classification helpers on
+//! `Error` rely on are listed here.
+
+pub const ER_CON_COUNT_ERROR: u32 = 1040;
+pub const ER_SERVER_SHUTDOWN: u32 = 1053;
+pub const ER_DUP_ENTRY: u32 = 1062;
+pub const ER_PARSE_ERROR: u32 = 1064;
+pub const ER_SYNTAX_ERROR: u32 = 1149;
+pub const ER_DUP_UNIQUE: u32 = 1169;
+pub const ER_TOO_MANY_USER_CONNECTIONS: u32 = 1203;
+pub const ER_LOCK_WAIT_TIMEOUT: u32 = 1205;
+pub const ER_LOCK_DEADLOCK: u32 = 1213;
+pub const ER_NO_REFERENCED_ROW: u32 = 1216;
+pub const ER_ROW_IS_REFERENCED: u32 = 1217;
+pub const ER_QUERY_INTERRUPTED: u32 = 1317;
+pub const ER_ROW_IS_REFERENCED_2: u32 = 1451;
+pub const ER_NO_REFERENCED_ROW_2: u32 = 1452;
+pub const ER_DUP_ENTRY_WITH_KEY_NAME: u32 = 1586;
+pub const ER_CLIENT_INTERACTION_TIMEOUT: u32 = 4031;
+
+pub const CR_CONNECTION_ERROR: u32 = 2002;
+pub const CR_CONN_HOST_ERROR: u32 = 2003;
+pub const CR_SERVER_GONE_ERROR: u32 = 2006;
+pub const CR_SERVER_LOST: u32 = 2013;
+pub const CR_SERVER_LOST_EXTENDED: u32 = 2055;
+
+pub(crate) const CONNECTION_LOST: &[u32] = &[
+    CR_SERVER_GONE_ERROR,
+    CR_SERVER_LOST,
+    CR_SERVER_LOST_EXTENDED,
+    ER_CLIENT_INTERACTION_TIMEOUT,
+];
+
+// failures where running the same work again, on a fresh connection if need
+// be, can succeed
+pub(crate) const RETRYABLE: &[u32] = &[
+    ER_LOCK_DEADLOCK,
+    ER_LOCK_WAIT_TIMEOUT,
+    ER_CON_COUNT_ERROR,
+    ER_TOO_MANY_USER_CONNECTIONS,
+    ER_SERVER_SHUTDOWN,
+    CR_CONNECTION_ERROR,
+    CR_CONN_HOST_ERROR,
+];
+
+pub(crate) const DUPLICATE_KEY: &[u32] = &[
+    ER_DUP_ENTRY,
+    ER_DUP_UNIQUE,
+    ER_DUP_ENTRY_WITH_KEY_NAME,
+];
+
+pub(crate) const FOREIGN_KEY_VIOLATION: &[u32] = &[
+    ER_NO_REFERENCED_ROW,
+    ER_ROW_IS_REFERENCED,
+    ER_ROW_IS_REFERENCED_2,
+    ER_NO_REFERENCED_ROW_2,
+];
+
+pub(crate) const SYNTAX_ERROR: &[u32] = &[
+    ER_PARSE_ERROR,
+    ER_SYNTAX_ERROR,
+];
@@ -0,0 +1,245 @@
+use super::mysql::enum_field_types::*;
+use super::connector::{Error, Row, Rows};
+use super::types::{Column, FromSql};
+use serde::de::{self, Deserializer, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::de::value::SeqDeserializer;
+use std::fmt;
+use std::str;
+use std::vec;
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error::DeserializeError(msg.to_string())
+    }
+}
+
+/// A result set deserializes as a sequence of rows.
+impl<'de> Deserializer<'de> for Rows {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(RowsAccess{ rows: self })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct
+        enum identifier ignored_any
+    }
+}
+
+struct RowsAccess {
+    rows: Rows,
+}
+
+impl<'de> SeqAccess<'de> for RowsAccess {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.rows.next() {
+            Some(mut row) => seed.deserialize(RowDeserializer::new(&mut row)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Deserializes a single row as a map from column name to cell, or as a
+/// tuple of its cells in column order.
+///
+/// Struct fields are looked up like `Row::get` does, so repeated column names
+/// resolve left to right and a virtual row only exposes its own columns.
+pub struct RowDeserializer<'a> {
+    row: &'a mut Row,
+}
+
+impl<'a> RowDeserializer<'a> {
+    pub fn new(row: &'a mut Row) -> Self {
+        RowDeserializer{ row }
+    }
+
+    fn visit_columns<'de, V: Visitor<'de>>(self, columns: Vec<(String, usize)>, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(RowAccess{ row: self.row, columns: columns.into_iter(), next: None })
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for RowDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let columns = self.row.columns().into_iter().map(|(i, _)| {
+            (self.row.true_fields().name(i).to_string(), i)
+        }).collect();
+        self.visit_columns(columns, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        // missing columns are left out, so serde reports them or applies defaults
+        let mut columns = vec![];
+        for field in fields {
+            if let Some(i) = self.row.take_column(field) {
+                columns.push((field.to_string(), i));
+            }
+        }
+        self.visit_columns(columns, visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let columns: Vec<usize> = self.row.columns().into_iter().map(|(i, _)| i).collect();
+        visitor.visit_seq(CellsAccess{ row: self.row, columns: columns.into_iter() })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf
+        option unit unit_struct enum identifier ignored_any
+    }
+}
+
+struct RowAccess<'a> {
+    row: &'a Row,
+    columns: vec::IntoIter<(String, usize)>,
+    next: Option<usize>,
+}
+
+impl<'de, 'a> MapAccess<'de> for RowAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.columns.next() {
+            Some((name, i)) => {
+                self.next = Some(i);
+                seed.deserialize(name.into_deserializer()).map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, Error> {
+        let i = self.next.take().expect("next_value_seed called before next_key_seed");
+        seed.deserialize(CellDeserializer::new(self.row, i))
+    }
+}
+
+struct CellsAccess<'a> {
+    row: &'a Row,
+    columns: vec::IntoIter<usize>,
+}
+
+impl<'de, 'a> SeqAccess<'de> for CellsAccess<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.columns.next() {
+            Some(i) => seed.deserialize(CellDeserializer::new(self.row, i)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+// a single cell. typed requests go through FromSql, so they convert and range
+// check exactly like Row::get; deserialize_any picks the visitor from the
+// column type instead.
+struct CellDeserializer<'a> {
+    value: Option<&'a [u8]>,
+    column: Column<'a>,
+}
+
+impl<'a> CellDeserializer<'a> {
+    fn new(row: &'a Row, index: usize) -> Self {
+        CellDeserializer{ value: row.cell(index), column: row.true_fields().column(index) }
+    }
+}
+
+macro_rules! deserialize_from_sql {
+    ($($method:ident => $visit:ident: $t:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(<$t>::from_sql(self.value, self.column)?)
+            }
+        )*
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for CellDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value = match self.value {
+            Some(value) => value,
+            None => return visitor.visit_none(),
+        };
+        match self.column.field_type() {
+            MYSQL_TYPE_NULL => visitor.visit_none(),
+            MYSQL_TYPE_BIT => visitor.visit_u64(u64::from_sql(self.value, self.column)?),
+            _ if self.column.is_integer() && self.column.is_unsigned() =>
+                visitor.visit_u64(u64::from_sql(self.value, self.column)?),
+            _ if self.column.is_integer() =>
+                visitor.visit_i64(i64::from_sql(self.value, self.column)?),
+            _ if self.column.is_float() =>
+                visitor.visit_f64(f64::from_sql(self.value, self.column)?),
+            // BLOB and BINARY columns carry the binary charset
+            _ if self.column.is_blob() => visitor.visit_bytes(value),
+            // text, DECIMAL (kept as a string so no precision is lost) and temporal types
+            _ => visitor.visit_str(str::from_utf8(value)?),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        let value = String::from_sql(self.value, self.column)?;
+        visitor.visit_enum(value.into_deserializer())
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Vec<u8> deserializes as a sequence, so hand it the cell byte by byte
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let value = Vec::<u8>::from_sql(self.value, self.column)?;
+        visitor.visit_seq(SeqDeserializer::new(value.into_iter()))
+    }
+
+    deserialize_from_sql! {
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+        deserialize_str => visit_string: String,
+        deserialize_string => visit_string: String,
+        deserialize_bytes => visit_byte_buf: Vec<u8>,
+        deserialize_byte_buf => visit_byte_buf: Vec<u8>,
+    }
+
+    forward_to_deserialize_any! {
+        char unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,44 @@
+use super::mysql;
+use std::ptr;
+use std::sync::Once;
+
+static LIBRARY: Once = Once::new();
+
+thread_local! {
+    static THREAD: ThreadInit = ThreadInit::new();
+}
+
+// mysql_thread_init for the current thread, undone when the thread exits
+struct ThreadInit;
+
+impl ThreadInit {
+    fn new() -> Self {
+        if unsafe{ mysql::mysql_thread_init() } != 0 {
+            panic!("mysql_thread_init failed");
+        }
+        ThreadInit
+    }
+}
+
+impl Drop for ThreadInit {
+    fn drop(&mut self) {
+        unsafe{ mysql::mysql_thread_end() };
+    }
+}
+
+/// Initializes libmysqlclient once per process and the calling thread once
+/// per thread. Must run before any client library call on a thread, since a
+/// `Connector` can be created on one thread and used on another.
+pub(crate) fn init() {
+    LIBRARY.call_once(|| {
+        // mysql_init would do this lazily, but that is not thread-safe
+        if unsafe{ mysql::mysql_server_init(0, ptr::null_mut(), ptr::null_mut()) } != 0 {
+            panic!("could not initialize libmysqlclient");
+        }
+        if unsafe{ mysql::mysql_thread_safe() } == 0 {
+            panic!("libmysqlclient was built without thread support");
+        }
+    });
+    // fails only while the thread is exiting, when it is too late to init anyway
+    let _ = THREAD.try_with(|_| ());
+}
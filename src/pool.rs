@@ -0,0 +1,286 @@
+use super::connector::{Connector, Error, WarningMode};
+use super::options::ConnectOptions;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Sizing and timeouts for a `Pool`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolOptions {
+    min_size: usize,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    acquire_timeout: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        PoolOptions{
+            min_size: 0,
+            max_size: 10,
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+            acquire_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl PoolOptions {
+    pub fn new() -> Self {
+        PoolOptions::default()
+    }
+
+    /// Connections opened up front and kept open even when idle.
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Most connections open at once, borrowed or idle.
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Idle connections beyond `min_size` are closed after this long.
+    pub fn idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Connections are closed instead of reused once they are this old.
+    pub fn max_lifetime(mut self, lifetime: Option<Duration>) -> Self {
+        self.max_lifetime = lifetime;
+        self
+    }
+
+    /// How long `Pool::get` waits for a connection when all are in use.
+    pub fn acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = timeout;
+        self
+    }
+}
+
+/// A thread-safe pool of connections opened with the same `ConnectOptions`.
+/// Cloning a `Pool` gives another handle to the same connections.
+///
+/// Idle connections are checked with `mysql_ping` before they are handed out,
+/// and session state is reset with `mysql_change_user` when a connection is
+/// returned, so borrowers never see each other's variables, temporary tables
+/// or open transactions. Idle and lifetime limits are enforced whenever a
+/// connection is taken or returned; there is no background thread.
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    connect: ConnectOptions,
+    opts: PoolOptions,
+    state: Mutex<PoolState>,
+    returned: Condvar,
+}
+
+struct PoolState {
+    idle: VecDeque<Idle>,
+    // borrowed and idle connections, plus ones being opened
+    open: usize,
+}
+
+struct Idle {
+    conn: PoolConnection,
+    since: Instant,
+}
+
+// a connector with the time it was opened
+struct PoolConnection {
+    conn: Connector,
+    created: Instant,
+}
+
+impl Pool {
+    pub fn new(connect: ConnectOptions, opts: PoolOptions) -> Result<Self, Error> {
+        if opts.min_size > opts.max_size {
+            return Err(Error::OptionError(format!("min_size {} is larger than max_size {}", opts.min_size, opts.max_size)));
+        }
+        let pool = Pool{ inner: Arc::new(PoolInner{
+            connect,
+            opts,
+            state: Mutex::new(PoolState{ idle: VecDeque::new(), open: 0 }),
+            returned: Condvar::new(),
+        }) };
+
+        for _ in 0..pool.inner.opts.min_size {
+            let conn = pool.inner.open()?;
+            let mut state = pool.inner.lock();
+            state.open += 1;
+            state.idle.push_back(Idle{ conn, since: Instant::now() });
+        }
+        Ok(pool)
+    }
+
+    /// Borrows a connection, opening a new one if none are idle and the pool
+    /// is not full. Fails with `Error::PoolTimeout` when none frees up within
+    /// the acquire timeout.
+    pub fn get(&self) -> Result<PooledConnector, Error> {
+        let deadline = Instant::now() + self.inner.opts.acquire_timeout;
+        let mut state = self.inner.lock();
+        loop {
+            // most recently returned first, so surplus connections go idle and expire
+            if let Some(idle) = state.idle.pop_back() {
+                // closing talks to the server, so it happens with the lock released
+                if self.inner.expired(&idle, state.open) {
+                    state.open -= 1;
+                    drop(state);
+                    drop(idle);
+                    state = self.inner.lock();
+                    continue;
+                }
+                drop(state);
+                let mut conn = idle.conn;
+                if conn.conn.ping().is_ok() {
+                    return Ok(PooledConnector{ pool: self.inner.clone(), conn: Some(conn) });
+                }
+                drop(conn);
+                state = self.inner.lock();
+                state.open -= 1;
+                continue;
+            }
+
+            if state.open < self.inner.opts.max_size {
+                state.open += 1;
+                drop(state);
+                return match self.inner.open() {
+                    Ok(conn) => Ok(PooledConnector{ pool: self.inner.clone(), conn: Some(conn) }),
+                    Err(err) => {
+                        self.inner.lock().open -= 1;
+                        self.inner.returned.notify_one();
+                        Err(err)
+                    },
+                };
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::PoolTimeout);
+            }
+            // recovers from poisoning for the same reason `PoolInner::lock` does
+            let (guard, _) = self.inner.returned.wait_timeout(state, deadline - now).unwrap_or_else(|err| err.into_inner());
+            state = guard;
+        }
+    }
+
+    /// Connections currently open, borrowed or idle.
+    pub fn size(&self) -> usize {
+        self.inner.lock().open
+    }
+
+    pub fn idle_count(&self) -> usize {
+        self.inner.lock().idle.len()
+    }
+}
+
+impl PoolInner {
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        // the state is only changed in small steps that leave it consistent
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn open(&self) -> Result<PoolConnection, Error> {
+        let mut conn = Connector::new();
+        conn.connect_with(&self.connect)?;
+        Ok(PoolConnection{ conn, created: Instant::now() })
+    }
+
+    fn too_old(&self, conn: &PoolConnection) -> bool {
+        self.opts.max_lifetime.is_some_and(|lifetime| conn.created.elapsed() >= lifetime)
+    }
+
+    // idle connections are kept past the idle timeout while `open` is within min_size
+    fn expired(&self, idle: &Idle, open: usize) -> bool {
+        self.too_old(&idle.conn) || (open > self.opts.min_size
+            && self.opts.idle_timeout.is_some_and(|timeout| idle.since.elapsed() >= timeout))
+    }
+
+    // puts session state back to how a fresh connection would have it
+    fn reset(&self, conn: &mut Connector) -> Result<(), Error> {
+        conn.change_user(
+            self.connect.get_user().unwrap_or(""),
+            self.connect.get_password(),
+            self.connect.get_db(),
+        )?;
+        if let Some(init_command) = self.connect.get_init_command() {
+            conn.execute(init_command)?;
+        }
+        conn.set_warning_mode(WarningMode::Ignore);
+        Ok(())
+    }
+
+    fn release(&self, mut conn: PoolConnection) {
+        let reusable = !self.too_old(&conn) && self.reset(&mut conn.conn).is_ok();
+
+        // closed once the lock is released, mysql_close talks to the server
+        let mut closed = vec![];
+        let mut state = self.lock();
+        if reusable {
+            state.idle.push_back(Idle{ conn, since: Instant::now() });
+        } else {
+            state.open -= 1;
+            closed.push(conn);
+        }
+
+        // close connections that sat idle too long, oldest first, down to min_size
+        while state.open > self.opts.min_size {
+            match state.idle.front() {
+                Some(idle) if self.expired(idle, state.open) => {},
+                _ => break,
+            }
+            closed.extend(state.idle.pop_front().map(|idle| idle.conn));
+            state.open -= 1;
+        }
+        drop(state);
+        self.returned.notify_one();
+        drop(closed);
+    }
+}
+
+/// A connection borrowed from a `Pool`. It derefs to `Connector` and goes
+/// back to the pool when dropped.
+pub struct PooledConnector {
+    pool: Arc<PoolInner>,
+    conn: Option<PoolConnection>,
+}
+
+impl PooledConnector {
+    /// Closes the connection instead of returning it to the pool, e.g. after
+    /// an error that left it in an unknown state.
+    pub fn discard(mut self) {
+        self.conn.take();
+        self.pool.lock().open -= 1;
+        self.pool.returned.notify_one();
+    }
+}
+
+impl Deref for PooledConnector {
+    type Target = Connector;
+
+    fn deref(&self) -> &Connector {
+        &self.conn.as_ref().unwrap().conn
+    }
+}
+
+impl DerefMut for PooledConnector {
+    fn deref_mut(&mut self) -> &mut Connector {
+        &mut self.conn.as_mut().unwrap().conn
+    }
+}
+
+impl Drop for PooledConnector {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.release(conn);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use super::connector::{Connector, Error};
+use super::transaction::Transaction;
+use std::cmp;
+use std::collections::hash_map::RandomState;
+use std::error;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+
+/// How often and how patiently `Connector::with_retrying_transaction` retries.
+///
+/// The wait before attempt `n + 1` starts at `initial_backoff` and doubles
+/// each time up to `max_backoff`. With jitter, a random amount of up to half
+/// of it is taken off, so clients that deadlocked on each other do not retry
+/// in lockstep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy{
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(2),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        RetryPolicy::default()
+    }
+
+    /// Total number of attempts, including the first. At least one is made.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The wait after `attempt` (counting from 1) failed.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        let backoff = self.initial_backoff.checked_mul(factor).unwrap_or(self.max_backoff);
+        let backoff = cmp::min(backoff, self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        // RandomState is seeded randomly, which is all the randomness this needs
+        let random = RandomState::new().build_hasher().finish();
+        let half = backoff / 2;
+        backoff - half.mul_f64((random >> 11) as f64 / (1u64 << 53) as f64)
+    }
+}
+
+/// A value produced by `with_retrying_transaction`, with the number of
+/// attempts it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retried<T> {
+    pub value: T,
+    pub attempts: u32,
+}
+
+/// The error that ended `with_retrying_transaction`, either because it was not
+/// retryable or because the attempts ran out.
+#[derive(Debug)]
+pub struct RetryError {
+    pub error: Error,
+    pub attempts: u32,
+}
+
+impl fmt::Display for RetryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (after {} attempt(s))", self.error, self.attempts)
+    }
+}
+
+impl error::Error for RetryError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<RetryError> for Error {
+    fn from(src: RetryError) -> Self {
+        src.error
+    }
+}
+
+impl Connector {
+    /// Runs `f` in a transaction and commits it, starting over in a fresh
+    /// transaction when `f` or the commit fails with an error for which
+    /// `Error::is_transaction_retryable` holds: a deadlock or lock wait timeout.
+    /// A deadlock rolls back the whole transaction, but a lock wait timeout
+    /// only the statement that timed out, unless `innodb_rollback_on_timeout`
+    /// is set; the failed attempt is rolled back in full before the next one.
+    ///
+    /// Other errors are returned as they are, a lost connection in particular:
+    /// when that happens during the commit, the transaction may well have been
+    /// committed, and running it again would apply it twice.
+    ///
+    /// `f` may run several times, so it should not have side effects outside
+    /// the database.
+    pub fn with_retrying_transaction<T, F>(&mut self, policy: &RetryPolicy, mut f: F) -> Result<Retried<T>, RetryError>
+        where F: FnMut(&mut Transaction) -> Result<T, Error>
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let result = self.transaction().and_then(|mut tx| {
+                let value = f(&mut tx)?;
+                tx.commit()?;
+                Ok(value)
+            });
+            match result {
+                Ok(value) => return Ok(Retried{ value, attempts }),
+                Err(ref err) if err.is_transaction_retryable() && attempts < policy.max_attempts => {
+                    thread::sleep(policy.backoff(attempts));
+                },
+                Err(err) => return Err(RetryError{ error: err, attempts }),
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use super::mysql;
+use super::init;
+use super::connector::{Connector, Error, Fields, Row, RealRow, ServerError, Storable};
+use std::os::raw::{c_char, c_ulong, c_void};
+use std::rc::Rc;
+
+const MYSQL_NO_DATA: i32 = 100;
+
+/// A value bound to a `?` placeholder of a prepared statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    // the returned bind points into `self` and `length`, so neither may move until execution
+    fn bind(&mut self, length: &mut c_ulong) -> mysql::MYSQL_BIND {
+        let mut bind = mysql::MYSQL_BIND::default();
+        match *self {
+            Value::Null => {
+                bind.buffer_type = mysql::enum_field_types::MYSQL_TYPE_NULL;
+            },
+            Value::Int(ref mut v) => {
+                bind.buffer_type = mysql::enum_field_types::MYSQL_TYPE_LONGLONG;
+                bind.buffer = v as *mut i64 as *mut c_void;
+            },
+            Value::UInt(ref mut v) => {
+                bind.buffer_type = mysql::enum_field_types::MYSQL_TYPE_LONGLONG;
+                bind.buffer = v as *mut u64 as *mut c_void;
+                bind.is_unsigned = 1;
+            },
+            Value::Float(ref mut v) => {
+                bind.buffer_type = mysql::enum_field_types::MYSQL_TYPE_DOUBLE;
+                bind.buffer = v as *mut f64 as *mut c_void;
+            },
+            Value::Bytes(ref mut v) => {
+                *length = v.len() as c_ulong;
+                bind.buffer_type = mysql::enum_field_types::MYSQL_TYPE_STRING;
+                bind.buffer = v.as_mut_ptr() as *mut c_void;
+                bind.buffer_length = v.len() as c_ulong;
+                bind.length = length;
+            },
+        }
+        bind
+    }
+}
+
+pub trait ToSql {
+    fn to_sql(&self) -> Value;
+}
+
+macro_rules! impl_to_sql {
+    ($variant:ident, $as:ty, $($t:ty),*) => {
+        $(
+            impl ToSql for $t {
+                fn to_sql(&self) -> Value {
+                    Value::$variant(*self as $as)
+                }
+            }
+        )*
+    }
+}
+
+impl_to_sql!(Int, i64, i8, i16, i32, i64, isize);
+impl_to_sql!(UInt, u64, u8, u16, u32, u64, usize);
+impl_to_sql!(Float, f64, f32, f64);
+
+impl ToSql for bool {
+    fn to_sql(&self) -> Value {
+        Value::Int(*self as i64)
+    }
+}
+
+impl ToSql for str {
+    fn to_sql(&self) -> Value {
+        Value::Bytes(self.as_bytes().to_vec())
+    }
+}
+
+impl ToSql for String {
+    fn to_sql(&self) -> Value {
+        Value::Bytes(self.as_bytes().to_vec())
+    }
+}
+
+impl ToSql for [u8] {
+    fn to_sql(&self) -> Value {
+        Value::Bytes(self.to_vec())
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn to_sql(&self) -> Value {
+        Value::Bytes(self.clone())
+    }
+}
+
+impl ToSql for Value {
+    fn to_sql(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl<T: ToSql + ?Sized> ToSql for &T {
+    fn to_sql(&self) -> Value {
+        (**self).to_sql()
+    }
+}
+
+impl<T: ToSql> ToSql for Option<T> {
+    fn to_sql(&self) -> Value {
+        match *self {
+            Some(ref v) => v.to_sql(),
+            None => Value::Null,
+        }
+    }
+}
+
+/// A server-side prepared statement. It borrows the `Connector` it was
+/// prepared on, so it cannot outlive the connection.
+pub struct Statement<'a> {
+    conn: &'a mut Connector,
+    stmt: *mut mysql::MYSQL_STMT,
+}
+
+impl<'a> Statement<'a> {
+    pub(crate) fn new(conn: &'a mut Connector, query: &[u8]) -> Result<Self, Error> {
+        init::init();
+        let stmt = unsafe{ mysql::mysql_stmt_init(conn.mysql) };
+        if stmt.is_null() {
+            return Err(Error::StatementError(ServerError::from_mysql(conn.mysql)));
+        }
+
+        let stmt = Statement{ conn: conn, stmt: stmt };
+        let ret = unsafe{
+            mysql::mysql_stmt_prepare(stmt.stmt, query.as_ptr() as *const c_char, query.len() as c_ulong)
+        };
+        if ret != 0 {
+            return Err(stmt.error());
+        }
+
+        Ok(stmt)
+    }
+
+    pub fn param_count(&self) -> usize {
+        unsafe{ mysql::mysql_stmt_param_count(self.stmt) as usize }
+    }
+
+    pub fn execute<T: Storable>(&mut self, params: &[&dyn ToSql]) -> Result<Vec<T::Kind>, Error> {
+        let expected = self.param_count();
+        if params.len() != expected {
+            return Err(Error::ParameterError(
+                format!("expected {} parameters, got {}", expected, params.len())
+            ));
+        }
+
+        let mut values: Vec<Value> = params.iter().map(|p| p.to_sql()).collect();
+        let mut lengths: Vec<c_ulong> = vec![0; values.len()];
+        let mut binds: Vec<mysql::MYSQL_BIND> = values.iter_mut()
+            .zip(lengths.iter_mut())
+            .map(|(value, length)| value.bind(length))
+            .collect();
+
+        if !binds.is_empty() && unsafe{ mysql::mysql_stmt_bind_param(self.stmt, binds.as_mut_ptr()) } != 0 {
+            return Err(self.error());
+        }
+        if unsafe{ mysql::mysql_stmt_execute(self.stmt) } != 0 {
+            return Err(self.error());
+        }
+
+        let meta = unsafe{ mysql::mysql_stmt_result_metadata(self.stmt) };
+        if meta.is_null() {
+            if unsafe{ mysql::mysql_stmt_errno(self.stmt) } != 0 {
+                return Err(self.error());
+            }
+            // statements like INSERT and UPDATE produce no result set
+            try!(self.conn.check_warnings());
+            return Ok(vec![]);
+        }
+
+        let result = self.fetch_all(meta).and_then(|rows| T::store(rows.into_iter()));
+        unsafe{
+            mysql::mysql_free_result(meta);
+            mysql::mysql_stmt_free_result(self.stmt);
+        }
+        let result = try!(result);
+        try!(self.conn.check_warnings());
+        Ok(result)
+    }
+
+    fn fetch_all(&mut self, meta: *mut mysql::st_mysql_res) -> Result<Vec<Row>, Error> {
+        let fields = Rc::new(try!(Fields::from_result(self.conn.mysql, meta)));
+        let count = fields.len();
+
+        if unsafe{ mysql::mysql_stmt_store_result(self.stmt) } != 0 {
+            return Err(self.error());
+        }
+
+        // every column is fetched as text with an empty buffer first, so the
+        // true length is known before the cell is read with mysql_stmt_fetch_column
+        let mut lengths: Vec<c_ulong> = vec![0; count];
+        let mut nulls: Vec<mysql::my_bool> = vec![0; count];
+        let mut binds: Vec<mysql::MYSQL_BIND> = lengths.iter_mut()
+            .zip(nulls.iter_mut())
+            .map(|(length, is_null)| {
+                mysql::MYSQL_BIND{
+                    buffer_type: mysql::enum_field_types::MYSQL_TYPE_STRING,
+                    length: length,
+                    is_null: is_null,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        if count > 0 && unsafe{ mysql::mysql_stmt_bind_result(self.stmt, binds.as_mut_ptr()) } != 0 {
+            return Err(self.error());
+        }
+
+        let mut rows = vec![];
+        loop {
+            let ret = unsafe{ mysql::mysql_stmt_fetch(self.stmt) };
+            if ret == MYSQL_NO_DATA {
+                break;
+            }
+            if ret == 1 {
+                return Err(self.error());
+            }
+
+            let mut cells = Vec::with_capacity(count);
+            for (i, bind) in binds.iter().enumerate() {
+                if unsafe{ *bind.is_null } != 0 {
+                    cells.push(None);
+                    continue;
+                }
+                let mut cell = vec![0u8; unsafe{ *bind.length } as usize];
+                if !cell.is_empty() {
+                    let mut column = *bind;
+                    column.buffer = cell.as_mut_ptr() as *mut c_void;
+                    column.buffer_length = cell.len() as c_ulong;
+                    if unsafe{ mysql::mysql_stmt_fetch_column(self.stmt, &mut column, i as u32, 0) } != 0 {
+                        return Err(self.error());
+                    }
+                }
+                cells.push(Some(cell));
+            }
+            rows.push(Row::RealRow(RealRow::from_cells(cells, fields.clone())));
+        }
+
+        Ok(rows)
+    }
+
+    fn error(&self) -> Error {
+        Error::StatementError(ServerError::from_stmt(self.stmt))
+    }
+}
+
+impl<'a> Drop for Statement<'a> {
+    fn drop(&mut self) {
+        unsafe{ mysql::mysql_stmt_close(self.stmt) };
+    }
+}
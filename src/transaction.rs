@@ -0,0 +1,175 @@
+use super::mysql;
+use super::init;
+use super::connector::{Connector, Error, ExecResult, RowStream, ServerError, Storable};
+use super::statement::Statement;
+use serde::de::DeserializeOwned;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    fn as_sql(self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+}
+
+/// How `Connector::transaction_with` starts a transaction. The defaults give a
+/// plain `START TRANSACTION` at the session's isolation level.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionOptions {
+    isolation_level: Option<IsolationLevel>,
+    read_only: bool,
+    consistent_snapshot: bool,
+}
+
+impl TransactionOptions {
+    pub fn new() -> Self {
+        TransactionOptions::default()
+    }
+
+    /// Applies to this transaction only, the session level is left alone.
+    pub fn isolation_level(mut self, level: IsolationLevel) -> Self {
+        self.isolation_level = Some(level);
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Takes the InnoDB snapshot right away instead of at the first read.
+    pub fn consistent_snapshot(mut self, consistent_snapshot: bool) -> Self {
+        self.consistent_snapshot = consistent_snapshot;
+        self
+    }
+
+    fn start_sql(&self) -> String {
+        let mut modes = vec![];
+        if self.consistent_snapshot {
+            modes.push("WITH CONSISTENT SNAPSHOT");
+        }
+        if self.read_only {
+            modes.push("READ ONLY");
+        }
+        if modes.is_empty() {
+            "START TRANSACTION".into()
+        } else {
+            format!("START TRANSACTION {}", modes.join(", "))
+        }
+    }
+}
+
+/// A transaction on a `Connector`, see `Connector::transaction`, or a
+/// savepoint nested inside one, see `Transaction::savepoint`.
+///
+/// The connector stays borrowed for as long as the transaction is open, and
+/// the transaction is rolled back when dropped without calling `commit`.
+pub struct Transaction<'a> {
+    conn: &'a mut Connector,
+    done: bool,
+    // 0 for the transaction itself, n for savepoint sp_n
+    depth: usize,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn begin(conn: &'a mut Connector, opts: &TransactionOptions) -> Result<Self, Error> {
+        if let Some(level) = opts.isolation_level {
+            conn.execute(format!("SET TRANSACTION ISOLATION LEVEL {}", level.as_sql()))?;
+        }
+        conn.execute(opts.start_sql())?;
+        Ok(Transaction{ conn, done: false, depth: 0 })
+    }
+
+    /// Starts a nested unit of work with `SAVEPOINT`. Committing it releases
+    /// the savepoint, dropping or rolling it back undoes only what ran since
+    /// it was taken and leaves the outer transaction open.
+    pub fn savepoint(&mut self) -> Result<Transaction<'_>, Error> {
+        let depth = self.depth + 1;
+        self.conn.execute(format!("SAVEPOINT sp_{}", depth))?;
+        Ok(Transaction{ conn: &mut *self.conn, done: false, depth })
+    }
+
+    pub fn is_savepoint(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub fn query<T: Storable>(&mut self, query: impl AsRef<[u8]>) -> Result<Vec<T::Kind>, Error> {
+        self.conn.query::<T>(query)
+    }
+
+    pub fn query_as<T: DeserializeOwned>(&mut self, query: impl AsRef<[u8]>) -> Result<Vec<T>, Error> {
+        self.conn.query_as(query)
+    }
+
+    pub fn query_iter(&mut self, query: impl AsRef<[u8]>) -> Result<RowStream<'_>, Error> {
+        self.conn.query_iter(query)
+    }
+
+    pub fn execute(&mut self, query: impl AsRef<[u8]>) -> Result<ExecResult, Error> {
+        self.conn.execute(query)
+    }
+
+    pub fn prepare(&mut self, query: impl AsRef<[u8]>) -> Result<Statement<'_>, Error> {
+        self.conn.prepare(query)
+    }
+
+    pub fn commit(mut self) -> Result<(), Error> {
+        self.done = true;
+        if self.is_savepoint() {
+            return self.conn.execute(format!("RELEASE SAVEPOINT sp_{}", self.depth)).map(|_| ());
+        }
+        init::init();
+        if unsafe{ mysql::mysql_commit(self.conn.mysql) } != 0 {
+            return Err(Error::QueryError(ServerError::from_mysql(self.conn.mysql)));
+        }
+        Ok(())
+    }
+
+    pub fn rollback(mut self) -> Result<(), Error> {
+        self.done = true;
+        self.undo()
+    }
+
+    fn undo(&mut self) -> Result<(), Error> {
+        if self.is_savepoint() {
+            self.conn.execute(format!("ROLLBACK TO SAVEPOINT sp_{}", self.depth))?;
+            return self.conn.execute(format!("RELEASE SAVEPOINT sp_{}", self.depth)).map(|_| ());
+        }
+        init::init();
+        if unsafe{ mysql::mysql_rollback(self.conn.mysql) } != 0 {
+            return Err(Error::QueryError(ServerError::from_mysql(self.conn.mysql)));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        if !self.done {
+            // nothing to report a failure to; a lost connection ends the transaction anyway
+            let _ = self.undo();
+        }
+    }
+}
+
+impl Connector {
+    /// Starts a transaction with `START TRANSACTION`.
+    pub fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
+        Transaction::begin(self, &TransactionOptions::new())
+    }
+
+    pub fn transaction_with(&mut self, opts: &TransactionOptions) -> Result<Transaction<'_>, Error> {
+        Transaction::begin(self, opts)
+    }
+}
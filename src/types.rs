@@ -0,0 +1,205 @@
+use super::mysql;
+use super::mysql::enum_field_types::*;
+use super::connector::Error;
+use std::ffi::CStr;
+use std::str;
+use std::str::FromStr;
+
+const UNSIGNED_FLAG: u32 = 32;
+const BINARY_CHARSET: u32 = 63;
+
+/// Metadata for the column a cell is being converted from.
+#[derive(Clone, Copy)]
+pub struct Column<'a> {
+    field: &'a mysql::st_mysql_field,
+}
+
+impl<'a> Column<'a> {
+    pub(crate) fn new(field: &'a mysql::st_mysql_field) -> Self {
+        Column{ field: field }
+    }
+
+    pub fn name(&self) -> &str {
+        unsafe{ CStr::from_ptr(self.field.name) }.to_str().unwrap_or("")
+    }
+
+    pub fn field_type(&self) -> mysql::enum_field_types {
+        self.field.type_
+    }
+
+    pub fn is_unsigned(&self) -> bool {
+        self.field.flags & UNSIGNED_FLAG != 0
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.field.charsetnr == BINARY_CHARSET
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        matches!(self.field.type_,
+            MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_LONG | MYSQL_TYPE_INT24 |
+            MYSQL_TYPE_LONGLONG | MYSQL_TYPE_YEAR)
+    }
+
+    pub(crate) fn is_float(&self) -> bool {
+        matches!(self.field.type_, MYSQL_TYPE_FLOAT | MYSQL_TYPE_DOUBLE)
+    }
+
+    pub(crate) fn is_decimal(&self) -> bool {
+        matches!(self.field.type_, MYSQL_TYPE_DECIMAL | MYSQL_TYPE_NEWDECIMAL)
+    }
+
+    fn is_string(&self) -> bool {
+        matches!(self.field.type_,
+            MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING | MYSQL_TYPE_STRING |
+            MYSQL_TYPE_TINY_BLOB | MYSQL_TYPE_MEDIUM_BLOB | MYSQL_TYPE_LONG_BLOB |
+            MYSQL_TYPE_BLOB | MYSQL_TYPE_ENUM | MYSQL_TYPE_SET)
+    }
+
+    pub(crate) fn is_text(&self) -> bool {
+        self.is_string() && !self.is_binary()
+    }
+
+    // numeric and temporal columns report the binary charset too, so only
+    // string types count as BLOB/BINARY
+    pub(crate) fn is_blob(&self) -> bool {
+        self.is_string() && self.is_binary()
+    }
+
+    fn error(&self, reason: &str) -> Error {
+        Error::ConversionError(format!(
+            "column {:?} ({:?}{}): {}",
+            self.name(),
+            self.field.type_,
+            if self.is_unsigned() { " UNSIGNED" } else { "" },
+            reason,
+        ))
+    }
+}
+
+/// Conversion from a single cell of a result row. `value` is `None` when the
+/// cell is SQL NULL.
+pub trait FromSql: Sized {
+    fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error>;
+}
+
+fn not_null<'a>(value: Option<&'a [u8]>, column: Column) -> Result<&'a [u8], Error> {
+    value.ok_or_else(|| column.error("unexpected NULL, use Option<T> for nullable columns"))
+}
+
+fn parse<T: FromStr>(value: &[u8], column: Column) -> Result<T, Error> {
+    let text = try!(str::from_utf8(value));
+    text.trim().parse().map_err(|_| column.error(&format!("cannot parse {:?}", text)))
+}
+
+// BIT columns arrive as big-endian raw bytes rather than text
+fn bits(value: &[u8], column: Column) -> Result<u64, Error> {
+    if value.len() > 8 {
+        return Err(column.error("BIT value wider than 64 bits"));
+    }
+    Ok(value.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+fn to_i64(value: &[u8], column: Column) -> Result<i64, Error> {
+    if let MYSQL_TYPE_BIT = column.field_type() {
+        let bits = try!(bits(value, column));
+        return if bits > i64::MAX as u64 {
+            Err(column.error("value out of range"))
+        } else {
+            Ok(bits as i64)
+        };
+    }
+    if !(column.is_integer() || column.is_decimal() || column.is_text()) {
+        return Err(column.error("not an integer column"));
+    }
+    if column.is_unsigned() {
+        let value: u64 = try!(parse(value, column));
+        if value > i64::MAX as u64 {
+            return Err(column.error("value out of range"));
+        }
+        Ok(value as i64)
+    } else {
+        parse(value, column)
+    }
+}
+
+fn to_u64(value: &[u8], column: Column) -> Result<u64, Error> {
+    if let MYSQL_TYPE_BIT = column.field_type() {
+        return bits(value, column);
+    }
+    if !(column.is_integer() || column.is_decimal() || column.is_text()) {
+        return Err(column.error("not an integer column"));
+    }
+    if column.is_unsigned() {
+        parse(value, column)
+    } else {
+        let value: i64 = try!(parse(value, column));
+        if value < 0 {
+            return Err(column.error("value out of range"));
+        }
+        Ok(value as u64)
+    }
+}
+
+macro_rules! from_sql_int {
+    ($via:ident, $wide:ty, $($t:ty),*) => {
+        $(
+            impl FromSql for $t {
+                fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error> {
+                    let value: $wide = try!($via(try!(not_null(value, column)), column));
+                    if value < <$t>::MIN as $wide || value > <$t>::MAX as $wide {
+                        return Err(column.error("value out of range"));
+                    }
+                    Ok(value as $t)
+                }
+            }
+        )*
+    }
+}
+
+from_sql_int!(to_i64, i64, i8, i16, i32, i64);
+from_sql_int!(to_u64, u64, u8, u16, u32, u64);
+
+impl FromSql for f64 {
+    fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error> {
+        let value = try!(not_null(value, column));
+        if !(column.is_float() || column.is_integer() || column.is_decimal() || column.is_text()) {
+            return Err(column.error("not a numeric column"));
+        }
+        parse(value, column)
+    }
+}
+
+impl FromSql for f32 {
+    fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error> {
+        f64::from_sql(value, column).map(|v| v as f32)
+    }
+}
+
+impl FromSql for bool {
+    fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error> {
+        i64::from_sql(value, column).map(|v| v != 0)
+    }
+}
+
+impl FromSql for String {
+    fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error> {
+        let value = try!(not_null(value, column));
+        Ok(try!(str::from_utf8(value)).into())
+    }
+}
+
+impl FromSql for Vec<u8> {
+    fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error> {
+        not_null(value, column).map(|v| v.to_vec())
+    }
+}
+
+impl<T: FromSql> FromSql for Option<T> {
+    fn from_sql(value: Option<&[u8]>, column: Column) -> Result<Self, Error> {
+        match value {
+            Some(_) => T::from_sql(value, column).map(Some),
+            None => Ok(None),
+        }
+    }
+}
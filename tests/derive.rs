@@ -0,0 +1,54 @@
+extern crate mysql;
+
+use mysql::{Connector, Storable};
+
+#[derive(Debug, Default, PartialEq, Storable)]
+struct Reply {
+    id: u64,
+    f_id: Option<u64>,
+    data: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Storable)]
+struct Renamed {
+    #[mysql(rename = "id")]
+    reply_id: u64,
+    #[mysql(default)]
+    missing: String,
+    #[mysql(default)]
+    null: u32,
+}
+
+#[derive(Debug, PartialEq, Storable)]
+struct Flattened {
+    #[mysql(flatten)]
+    reply: Reply,
+    extra: i32,
+}
+
+#[test]
+fn test_derive() {
+    let mut mysql = Connector::new();
+    mysql.connect("root:password@127.0.0.1/test").unwrap();
+
+    let results = mysql.query::<Reply>("SELECT 1 as id, 2 as f_id, 'x' as data").unwrap();
+    assert_eq!(results, vec![Reply{ id: 1, f_id: Some(2), data: Some("x".into()) }]);
+
+    let results = mysql.query::<Renamed>("SELECT 1 as id, NULL as `null`").unwrap();
+    assert_eq!(results, vec![Renamed{ reply_id: 1, missing: String::new(), null: 0 }]);
+
+    let results = mysql.query::<Flattened>("SELECT 1 as id, NULL as f_id, NULL as data, -3 as extra").unwrap();
+    assert_eq!(results, vec![Flattened{ reply: Reply{ id: 1, f_id: None, data: None }, extra: -3 }]);
+}
+
+#[test]
+fn test_derive_conversion_error() {
+    let mut mysql = Connector::new();
+    mysql.connect("root:password@127.0.0.1/test").unwrap();
+
+    let result = mysql.query::<Reply>("SELECT -1 as id, NULL as f_id, NULL as data");
+    assert!(result.is_err());
+
+    let result = mysql.query::<Reply>("SELECT NULL as id, NULL as f_id, NULL as data");
+    assert!(result.is_err());
+}
@@ -0,0 +1,111 @@
+extern crate mysql;
+
+use mysql::{ConnectOptions, Connector, Protocol};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// a throwaway mysqld listening only on a socket in a temp directory. set
+// MYSQLD to the server binary if it is not on the PATH.
+struct Server {
+    dir: PathBuf,
+    child: Child,
+}
+
+impl Server {
+    fn start() -> Option<Server> {
+        let mysqld = env::var("MYSQLD").unwrap_or_else(|_| "mysqld".into());
+        let dir = env::temp_dir().join(format!("mysql-socket-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let data = dir.join("data");
+
+        let init = Command::new(&mysqld)
+            .arg("--no-defaults")
+            .arg("--initialize-insecure")
+            .arg("--user=root")
+            .arg(format!("--datadir={}", data.display()))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        match init {
+            Ok(ref status) if status.success() => {},
+            _ => {
+                println!("skipping: could not initialize a datadir with {}", mysqld);
+                let _ = fs::remove_dir_all(&dir);
+                return None;
+            },
+        }
+
+        let child = Command::new(&mysqld)
+            .arg("--no-defaults")
+            .arg("--user=root")
+            .arg("--skip-networking")
+            .arg(format!("--datadir={}", data.display()))
+            .arg(format!("--socket={}", dir.join("mysqld.sock").display()))
+            .arg(format!("--pid-file={}", dir.join("mysqld.pid").display()))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let server = Server{ dir: dir, child: child };
+
+        let started = Instant::now();
+        while !server.socket().exists() {
+            assert!(started.elapsed() < Duration::from_secs(60), "mysqld did not create its socket");
+            thread::sleep(Duration::from_millis(100));
+        }
+        Some(server)
+    }
+
+    fn socket(&self) -> PathBuf {
+        self.dir.join("mysqld.sock")
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn select_one(mysql: &mut Connector) -> i64 {
+    let results: Vec<(i64,)> = mysql.query_as("SELECT 1").unwrap();
+    results[0].0
+}
+
+#[test]
+fn test_socket_connections() {
+    let server = match Server::start() {
+        Some(server) => server,
+        None => return,
+    };
+    let socket = server.socket().to_str().unwrap().to_string();
+
+    let mut mysql = Connector::new();
+    mysql.connect(format!("mysql://root@localhost/mysql?socket={}", socket)).unwrap();
+    assert_eq!(select_one(&mut mysql), 1);
+
+    // the socket path can also stand in for the host
+    let mut mysql = Connector::new();
+    mysql.connect(format!("mysql://root@{}/mysql", socket.replace('/', "%2F"))).unwrap();
+    assert_eq!(select_one(&mut mysql), 1);
+
+    let opts = ConnectOptions::new()
+        .user("root")
+        .db("mysql")
+        .socket(socket.clone())
+        .protocol(Protocol::Socket);
+    let mut mysql = Connector::new();
+    mysql.connect_with(&opts).unwrap();
+    assert_eq!(select_one(&mut mysql), 1);
+
+    // forcing TCP ignores the socket; nothing listens on port 9
+    let mut mysql = Connector::new();
+    assert!(mysql.connect_with(&opts.port(9).protocol(Protocol::Tcp)).is_err());
+}